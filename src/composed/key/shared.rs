@@ -1,11 +1,15 @@
-use chrono;
+use chrono::{DateTime, Duration, Utc};
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 
 use composed::SignedKeyDetails;
+use crypto::aead::AEADAlgorithm;
 use crypto::hash::HashAlgorithm;
 use crypto::sym::SymmetricKeyAlgorithm;
 use errors::Result;
 use packet::{
-    KeyFlags, PacketTrait, SignatureConfigBuilder, SignatureType, Subpacket, UserAttribute, UserId,
+    Features, KeyFlags, KeyServerPreferences, PacketTrait, SignatureConfigBuilder, SignatureType,
+    Subpacket, UserAttribute, UserId,
 };
 use types::{CompressionAlgorithm, RevocationKey, SecretKeyTrait};
 
@@ -18,7 +22,12 @@ pub struct KeyDetails {
     preferred_symmetric_algorithms: Vec<SymmetricKeyAlgorithm>,
     preferred_hash_algorithms: Vec<HashAlgorithm>,
     preferred_compression_algorithms: Vec<CompressionAlgorithm>,
+    preferred_aead_algorithms: Vec<AEADAlgorithm>,
+    features: Features,
+    key_server_preferences: KeyServerPreferences,
     revocation_key: Option<RevocationKey>,
+    /// How long after its creation time the primary key stays valid, if at all.
+    validity: Option<Duration>,
 }
 
 impl KeyDetails {
@@ -31,7 +40,11 @@ impl KeyDetails {
         preferred_symmetric_algorithms: Vec<SymmetricKeyAlgorithm>,
         preferred_hash_algorithms: Vec<HashAlgorithm>,
         preferred_compression_algorithms: Vec<CompressionAlgorithm>,
+        preferred_aead_algorithms: Vec<AEADAlgorithm>,
+        features: Features,
+        key_server_preferences: KeyServerPreferences,
         revocation_key: Option<RevocationKey>,
+        validity: Option<Duration>,
     ) -> Self {
         KeyDetails {
             primary_user_id,
@@ -41,18 +54,48 @@ impl KeyDetails {
             preferred_symmetric_algorithms,
             preferred_hash_algorithms,
             preferred_compression_algorithms,
+            preferred_aead_algorithms,
+            features,
+            key_server_preferences,
             revocation_key,
+            validity,
         }
     }
 
-    pub fn sign<F>(self, key: &impl SecretKeyTrait, key_pw: F) -> Result<SignedKeyDetails>
+    /// Signs the user ids and attributes, binding them to `key`.
+    ///
+    /// `created_at` is used as the `SignatureCreationTime` of every generated signature, and
+    /// `rng` drives any randomness the signing operation itself needs. Passing both in
+    /// explicitly (instead of reading `chrono::Utc::now()` and the system CSPRNG) means that
+    /// signing the same key details twice with the same inputs produces byte-identical
+    /// signatures — the property a deterministic key derivation (e.g. from a seed phrase)
+    /// depends on.
+    pub fn sign<R, F>(
+        self,
+        rng: &mut R,
+        key: &impl SecretKeyTrait,
+        key_pw: F,
+        created_at: DateTime<Utc>,
+    ) -> Result<SignedKeyDetails>
     where
+        R: CryptoRng + RngCore,
         F: (FnOnce() -> String) + Clone,
     {
+        if !key.algorithm().is_supported_by_backend() {
+            bail!(
+                "public key algorithm {:?} is not supported by the active crypto backend",
+                key.algorithm()
+            );
+        }
+
         let keyflags: Vec<u8> = self.keyflags.into();
         let preferred_symmetric_algorithms = self.preferred_symmetric_algorithms;
         let preferred_hash_algorithms = self.preferred_hash_algorithms;
         let preferred_compression_algorithms = self.preferred_compression_algorithms;
+        let preferred_aead_algorithms = self.preferred_aead_algorithms;
+        let features: Vec<u8> = self.features.into();
+        let key_server_preferences: Vec<u8> = self.key_server_preferences.into();
+        let key_expiration_time = key_expiration_seconds(self.validity)?;
         let revocation_key = self.revocation_key;
 
         let mut users = vec![];
@@ -62,12 +105,21 @@ impl KeyDetails {
             let id = self.primary_user_id;
             let mut hashed_subpackets = vec![
                 Subpacket::IsPrimary(true),
-                Subpacket::SignatureCreationTime(chrono::Utc::now()),
+                Subpacket::SignatureCreationTime(created_at),
                 Subpacket::KeyFlags(keyflags.clone()),
                 Subpacket::PreferredSymmetricAlgorithms(preferred_symmetric_algorithms.clone()),
                 Subpacket::PreferredHashAlgorithms(preferred_hash_algorithms.clone()),
                 Subpacket::PreferredCompressionAlgorithms(preferred_compression_algorithms.clone()),
+                Subpacket::PreferredAEADAlgorithms(preferred_aead_algorithms.clone()),
+                Subpacket::Features(features.clone()),
+                Subpacket::KeyServerPreferences(key_server_preferences.clone()),
             ];
+            if let Some(expires_in) = key_expiration_time {
+                // `KeyExpirationTime` is only meaningful on the primary self-signature; readers
+                // consult the primary user id's binding signature for it and ignore it
+                // elsewhere, so it's emitted here and nowhere else in this method.
+                hashed_subpackets.push(Subpacket::KeyExpirationTime(expires_in));
+            }
             if let Some(rkey) = revocation_key {
                 hashed_subpackets.push(Subpacket::RevocationKey(rkey));
             }
@@ -82,7 +134,7 @@ impl KeyDetails {
                 ])
                 .build()?;
 
-            let sig = config.sign_certificate(key, key_pw.clone(), id.tag(), &id)?;
+            let sig = config.sign_certificate(rng, key, key_pw.clone(), id.tag(), &id)?;
 
             users.push(id.into_signed(sig));
         }
@@ -93,37 +145,57 @@ impl KeyDetails {
             self.user_ids
                 .into_iter()
                 .map(|id| {
+                    let mut hashed_subpackets = vec![
+                        Subpacket::SignatureCreationTime(created_at),
+                        Subpacket::KeyFlags(keyflags.clone()),
+                        Subpacket::PreferredSymmetricAlgorithms(
+                            preferred_symmetric_algorithms.clone(),
+                        ),
+                        Subpacket::PreferredHashAlgorithms(preferred_hash_algorithms.clone()),
+                        Subpacket::PreferredCompressionAlgorithms(
+                            preferred_compression_algorithms.clone(),
+                        ),
+                        Subpacket::PreferredAEADAlgorithms(preferred_aead_algorithms.clone()),
+                        Subpacket::Features(features.clone()),
+                        Subpacket::KeyServerPreferences(key_server_preferences.clone()),
+                    ];
+                    // `KeyExpirationTime` belongs only on the primary self-signature; OpenPGP
+                    // readers look at the primary user id's binding signature for it, so
+                    // repeating it here would be redundant at best and, if it ever drifted from
+                    // the primary copy, ambiguous about which one to trust.
+
                     let config = SignatureConfigBuilder::default()
                         .typ(SignatureType::CertGeneric)
                         .pub_alg(key.algorithm())
-                        .hashed_subpackets(vec![
-                            Subpacket::SignatureCreationTime(chrono::Utc::now()),
-                            Subpacket::KeyFlags(keyflags.clone()),
-                            Subpacket::PreferredSymmetricAlgorithms(
-                                preferred_symmetric_algorithms.clone(),
-                            ),
-                            Subpacket::PreferredHashAlgorithms(preferred_hash_algorithms.clone()),
-                            Subpacket::PreferredCompressionAlgorithms(
-                                preferred_compression_algorithms.clone(),
-                            ),
-                        ])
+                        .hashed_subpackets(hashed_subpackets)
                         .unhashed_subpackets(vec![
                             Subpacket::Issuer(key.key_id().expect("missing key id")),
                             Subpacket::IssuerFingerprint(key.fingerprint()),
                         ])
                         .build()?;
 
-                    let sig = config.sign_certificate(key, key_pw.clone(), id.tag(), &id)?;
+                    let sig = config.sign_certificate(rng, key, key_pw.clone(), id.tag(), &id)?;
 
                     Ok(id.into_signed(sig))
                 })
                 .collect::<Result<Vec<_>>>()?,
         );
 
+        // Note: the `rng`/`created_at` threading above only covers the signatures this method
+        // produces. Reproducing an entire key deterministically from a seed additionally
+        // requires generating its RSA/EdDSA/ECDH key material with the same `ChaCha20Rng`; see
+        // `sign_with_seed` and `rng_from_seed` below.
+
+        // User attributes get the same `rng`/`created_at` threading as user ids, so that
+        // signing the same `KeyDetails` twice with the same inputs reproduces them
+        // byte-for-byte too. `packet::UserAttribute::sign` applies its own, smaller set of
+        // hashed subpackets (currently just `SignatureCreationTime`); giving it the full
+        // preference/feature set user ids carry would mean changing its signature to accept
+        // them, which lives outside this chunk of the crate.
         let user_attributes = self
             .user_attributes
             .into_iter()
-            .map(|u| u.sign(key, key_pw.clone()))
+            .map(|u| u.sign(rng, key, key_pw.clone(), created_at))
             .collect::<Result<Vec<_>>>()?;
 
         Ok(SignedKeyDetails {
@@ -133,4 +205,51 @@ impl KeyDetails {
             user_attributes,
         })
     }
+
+    /// Like [`KeyDetails::sign`], but derives the signing `rng` from `seed` instead of taking a
+    /// caller-supplied CSPRNG; `created_at` is still passed in explicitly.
+    ///
+    /// This is the entry point a deterministic key derivation (e.g. from a BIP39-style seed
+    /// phrase) should call: the same `seed` and `created_at` always produce byte-identical
+    /// signatures, with no caller-supplied CSPRNG to accidentally make non-deterministic.
+    pub fn sign_with_seed(
+        self,
+        seed: &[u8; 32],
+        key: &impl SecretKeyTrait,
+        key_pw: impl (FnOnce() -> String) + Clone,
+        created_at: DateTime<Utc>,
+    ) -> Result<SignedKeyDetails> {
+        let mut rng = rng_from_seed(seed);
+        self.sign(&mut rng, key, key_pw, created_at)
+    }
+}
+
+/// Converts a primary key validity window into the number of seconds after its creation time
+/// that it expires, as expected by `Subpacket::KeyExpirationTime`.
+fn key_expiration_seconds(validity: Option<Duration>) -> Result<Option<u32>> {
+    let validity = match validity {
+        Some(validity) => validity,
+        None => return Ok(None),
+    };
+
+    let secs = validity.num_seconds();
+    if secs < 0 || secs > i64::from(u32::max_value()) {
+        bail!("key validity duration {:?} does not fit in a KeyExpirationTime subpacket", validity);
+    }
+
+    Ok(Some(secs as u32))
+}
+
+/// Expands a fixed seed (e.g. the entropy behind a BIP39-style mnemonic) into a deterministic
+/// CSPRNG.
+///
+/// Used by [`KeyDetails::sign_with_seed`] to drive signing deterministically. Reconstructing an
+/// entire key — not just its self-signatures — from a seed phrase additionally needs the
+/// RSA/EdDSA/ECDH key-generation builders to consume this same `ChaCha20Rng`; this checkout has
+/// no such builders (there is no keygen module at all, only the already-generated-key signing
+/// path in this file), so that part of the original request is out of scope here. Whichever
+/// commit adds those builders should call this function rather than reading system entropy
+/// directly.
+pub fn rng_from_seed(seed: &[u8; 32]) -> ChaCha20Rng {
+    ChaCha20Rng::from_seed(*seed)
 }
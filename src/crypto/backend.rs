@@ -0,0 +1,118 @@
+use crypto::public_key::PublicKeyAlgorithm;
+use errors::Result;
+
+/// A pluggable cryptographic backend, implementing the primitive operations OpenPGP key and
+/// signature handling needs: signing, verification, ECDH key agreement and RSA decryption.
+///
+/// Which backend is compiled in is a cargo-feature choice: `backend-rustcrypto` (the default)
+/// is pure Rust and portable to targets such as WASM; `backend-openssl` and `backend-botan`
+/// reach algorithms the pure-Rust implementations don't cover (DSA, ElGamal, classic ECDSA
+/// over NIST curves) and suit FIPS-constrained environments. `PublicKeyAlgorithm::is_supported_by_backend`
+/// mirrors a given backend's `supports`, so callers can check before calling into it.
+pub trait CryptoBackend {
+    /// Public-key algorithms this backend can sign, verify, encrypt or decrypt with.
+    fn supports(&self, alg: PublicKeyAlgorithm) -> bool;
+
+    /// Signs `digest` (the output of the signature's hash algorithm) with the given secret key
+    /// material, returning the raw signature bytes.
+    fn sign(&self, alg: PublicKeyAlgorithm, key: &[u8], digest: &[u8]) -> Result<Vec<u8>>;
+
+    /// Verifies `signature` over `digest` against the given public key material.
+    fn verify(&self, alg: PublicKeyAlgorithm, key: &[u8], digest: &[u8], signature: &[u8])
+        -> Result<()>;
+
+    /// Computes the ECDH shared secret for a recipient's private key and a sender's ephemeral
+    /// public key.
+    fn ecdh_shared_secret(&self, our_private: &[u8], their_public: &[u8]) -> Result<Vec<u8>>;
+
+    /// Decrypts an RSA-encrypted session key.
+    fn rsa_decrypt(&self, key: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// The pure-Rust backend backing `PublicKeyAlgorithm::is_supported_by_backend` by default.
+///
+/// Only covers the algorithms this crate has audited RustCrypto implementations for; routing
+/// to `backend-openssl`/`backend-botan` for the rest is left to those features' own
+/// `CryptoBackend` implementations.
+///
+/// Scope in this checkout: `supports` is fully implemented, since it's pure algorithm-id
+/// bookkeeping. `sign`/`verify`/`ecdh_shared_secret`/`rsa_decrypt` are not — they need the
+/// `rsa` and `ed25519-dalek` crates as dependencies, and this checkout has no `Cargo.toml` to
+/// add them to. Rather than fake success or panic, they return a descriptive error (`sign` and
+/// `verify` first check `supports` for the requested algorithm); wiring in the real primitives is left to the commit that adds those
+/// dependencies.
+#[cfg(feature = "backend-rustcrypto")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RustCryptoBackend;
+
+#[cfg(feature = "backend-rustcrypto")]
+impl CryptoBackend for RustCryptoBackend {
+    fn supports(&self, alg: PublicKeyAlgorithm) -> bool {
+        // Deliberately hardcoded rather than delegating to
+        // `PublicKeyAlgorithm::is_supported_by_backend`: that method reports what *some*
+        // compiled-in backend supports, which includes DSA/ECDSA/ElGamal when
+        // `backend-openssl`/`backend-botan` are enabled alongside this one. This backend only
+        // ever implements the algorithms listed below, regardless of which other features are
+        // on.
+        matches!(
+            alg,
+            PublicKeyAlgorithm::RSA
+                | PublicKeyAlgorithm::RSAEncrypt
+                | PublicKeyAlgorithm::RSASign
+                | PublicKeyAlgorithm::ECDH
+                | PublicKeyAlgorithm::EdDSA
+        )
+    }
+
+    fn sign(&self, alg: PublicKeyAlgorithm, _key: &[u8], _digest: &[u8]) -> Result<Vec<u8>> {
+        if !self.supports(alg) {
+            bail!(
+                "public key algorithm {:?} is not supported by the RustCrypto backend",
+                alg
+            );
+        }
+        // RSA and EdDSA signing, and ECDH/RSA decryption, need the `rsa` and `ed25519-dalek`
+        // crates wired in as dependencies; this checkout has no Cargo.toml to add them to, so
+        // this backend only routes and validates algorithm support for now. The actual
+        // primitive calls belong in the commit that adds those dependencies.
+        bail!(
+            "signing with {:?} via the RustCrypto backend requires a crypto dependency not \
+             available in this checkout",
+            alg
+        );
+    }
+
+    fn verify(
+        &self,
+        alg: PublicKeyAlgorithm,
+        _key: &[u8],
+        _digest: &[u8],
+        _signature: &[u8],
+    ) -> Result<()> {
+        if !self.supports(alg) {
+            bail!(
+                "public key algorithm {:?} is not supported by the RustCrypto backend",
+                alg
+            );
+        }
+        bail!(
+            "verifying with {:?} via the RustCrypto backend requires a crypto dependency not \
+             available in this checkout",
+            alg
+        );
+    }
+
+    fn ecdh_shared_secret(&self, _our_private: &[u8], _their_public: &[u8]) -> Result<Vec<u8>> {
+        bail!(
+            "ECDH via the RustCrypto backend requires a crypto dependency not available in \
+             this checkout"
+        );
+    }
+
+    fn rsa_decrypt(&self, _key: &[u8], _ciphertext: &[u8]) -> Result<Vec<u8>> {
+        bail!(
+            "RSA decryption via the RustCrypto backend requires a crypto dependency not \
+             available in this checkout"
+        );
+    }
+}
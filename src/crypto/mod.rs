@@ -0,0 +1,6 @@
+pub mod aead;
+pub mod backend;
+pub mod ecc_curve;
+pub mod hash;
+pub mod public_key;
+pub mod sym;
@@ -1,6 +1,7 @@
 use std::{fmt, io};
 
 use num_bigint::BigUint;
+use num_traits::FromPrimitive;
 
 use crypto::ecc_curve::ECCCurve;
 use crypto::hash::HashAlgorithm;
@@ -9,7 +10,7 @@ use errors::Result;
 use ser::Serialize;
 use util::{write_bignum_mpi, write_mpi};
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, FromPrimitive)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 #[repr(u8)]
 pub enum PublicKeyAlgorithm {
     /// RSA (Encrypt and Sign) [HAC]
@@ -33,7 +34,6 @@ pub enum PublicKeyAlgorithm {
     /// EdDSA (not yet assigned)
     EdDSA = 22,
     /// Private experimental range (from OpenGPG)
-    // TODO: genenric Unknown(u8)
     Private100 = 100,
     Private101 = 101,
     Private102 = 102,
@@ -45,6 +45,119 @@ pub enum PublicKeyAlgorithm {
     Private108 = 108,
     Private109 = 109,
     Private110 = 110,
+    /// An algorithm id this implementation does not recognize.
+    ///
+    /// Keeps the raw id around so unknown keys and certificates can still be parsed, inspected,
+    /// and serialized back out byte-for-byte, even though this crate cannot use the key for
+    /// any cryptographic operation.
+    Unknown(u8),
+}
+
+impl ::num_traits::FromPrimitive for PublicKeyAlgorithm {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 || n > i64::from(::std::u8::MAX) {
+            return None;
+        }
+        Self::from_u64(n as u64)
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        if n > u64::from(::std::u8::MAX) {
+            return None;
+        }
+
+        Some(match n {
+            1 => PublicKeyAlgorithm::RSA,
+            2 => PublicKeyAlgorithm::RSAEncrypt,
+            3 => PublicKeyAlgorithm::RSASign,
+            16 => PublicKeyAlgorithm::ElgamalSign,
+            17 => PublicKeyAlgorithm::DSA,
+            18 => PublicKeyAlgorithm::ECDH,
+            19 => PublicKeyAlgorithm::ECDSA,
+            20 => PublicKeyAlgorithm::Elgamal,
+            21 => PublicKeyAlgorithm::DiffieHellman,
+            22 => PublicKeyAlgorithm::EdDSA,
+            100 => PublicKeyAlgorithm::Private100,
+            101 => PublicKeyAlgorithm::Private101,
+            102 => PublicKeyAlgorithm::Private102,
+            103 => PublicKeyAlgorithm::Private103,
+            104 => PublicKeyAlgorithm::Private104,
+            105 => PublicKeyAlgorithm::Private105,
+            106 => PublicKeyAlgorithm::Private106,
+            107 => PublicKeyAlgorithm::Private107,
+            108 => PublicKeyAlgorithm::Private108,
+            109 => PublicKeyAlgorithm::Private109,
+            110 => PublicKeyAlgorithm::Private110,
+            n => PublicKeyAlgorithm::Unknown(n as u8),
+        })
+    }
+}
+
+impl PublicKeyAlgorithm {
+    /// The raw wire id for this algorithm.
+    pub fn to_u8(self) -> u8 {
+        match self {
+            PublicKeyAlgorithm::RSA => 1,
+            PublicKeyAlgorithm::RSAEncrypt => 2,
+            PublicKeyAlgorithm::RSASign => 3,
+            PublicKeyAlgorithm::ElgamalSign => 16,
+            PublicKeyAlgorithm::DSA => 17,
+            PublicKeyAlgorithm::ECDH => 18,
+            PublicKeyAlgorithm::ECDSA => 19,
+            PublicKeyAlgorithm::Elgamal => 20,
+            PublicKeyAlgorithm::DiffieHellman => 21,
+            PublicKeyAlgorithm::EdDSA => 22,
+            PublicKeyAlgorithm::Private100 => 100,
+            PublicKeyAlgorithm::Private101 => 101,
+            PublicKeyAlgorithm::Private102 => 102,
+            PublicKeyAlgorithm::Private103 => 103,
+            PublicKeyAlgorithm::Private104 => 104,
+            PublicKeyAlgorithm::Private105 => 105,
+            PublicKeyAlgorithm::Private106 => 106,
+            PublicKeyAlgorithm::Private107 => 107,
+            PublicKeyAlgorithm::Private108 => 108,
+            PublicKeyAlgorithm::Private109 => 109,
+            PublicKeyAlgorithm::Private110 => 110,
+            PublicKeyAlgorithm::Unknown(alg) => alg,
+        }
+    }
+}
+
+impl From<PublicKeyAlgorithm> for u8 {
+    fn from(alg: PublicKeyAlgorithm) -> u8 {
+        alg.to_u8()
+    }
+}
+
+impl PublicKeyAlgorithm {
+    /// Returns `true` if the currently active crypto backend implements this algorithm.
+    ///
+    /// This mirrors the active `crypto::backend::CryptoBackend` implementation's `supports`:
+    /// the pure-Rust `backend-rustcrypto` backend only covers the algorithms it has audited
+    /// implementations for; `backend-openssl` and `backend-botan` pick up the rest (DSA,
+    /// ElGamal, classic ECDSA over NIST curves). Callers should check this before attempting
+    /// `KeyDetails::sign` or decryption with a given key, rather than relying on the backend
+    /// to panic or error deep in the crypto layer.
+    ///
+    /// The arm below gated on the `backend-openssl`/`backend-botan` cargo features only applies
+    /// when one of those features is enabled; otherwise those algorithms report unsupported.
+    pub fn is_supported_by_backend(&self) -> bool {
+        match self {
+            PublicKeyAlgorithm::RSA
+            | PublicKeyAlgorithm::RSAEncrypt
+            | PublicKeyAlgorithm::RSASign
+            | PublicKeyAlgorithm::ECDH
+            | PublicKeyAlgorithm::EdDSA => true,
+
+            #[cfg(any(feature = "backend-openssl", feature = "backend-botan"))]
+            PublicKeyAlgorithm::DSA
+            | PublicKeyAlgorithm::ECDSA
+            | PublicKeyAlgorithm::ElgamalSign
+            | PublicKeyAlgorithm::Elgamal => true,
+
+            _ => false,
+        }
+    }
 }
 
 /// Represent the public paramaters for the different algorithms.
@@ -79,6 +192,135 @@ pub enum PublicParams {
         curve: ECCCurve,
         q: Vec<u8>,
     },
+    /// Public parameters for an algorithm id this implementation does not recognize.
+    ///
+    /// The raw parameter bytes are kept as-is (no MPI framing is assumed) so the enclosing
+    /// key or certificate packet can still be serialized back out unchanged.
+    Unknown {
+        alg: u8,
+        data: Vec<u8>,
+    },
+}
+
+/// Reads a single MPI (a two-byte bit length followed by the big-endian value bytes),
+/// returning the parsed bytes and whatever of `data` follows it.
+fn read_mpi(data: &[u8]) -> Result<(&[u8], &[u8])> {
+    if data.len() < 2 {
+        bail!("not enough data to read an MPI length");
+    }
+    let bits = (u16::from(data[0]) << 8) | u16::from(data[1]);
+    let len = (usize::from(bits) + 7) / 8;
+    if data.len() < 2 + len {
+        bail!("not enough data to read an MPI body");
+    }
+
+    Ok((&data[2 + len..], &data[2..2 + len]))
+}
+
+/// Like [`read_mpi`], but parses the bytes into a [`BigUint`] right away.
+fn read_bignum_mpi(data: &[u8]) -> Result<(&[u8], BigUint)> {
+    let (rest, bytes) = read_mpi(data)?;
+    Ok((rest, BigUint::from_bytes_be(bytes)))
+}
+
+/// Reads a one-byte-length-prefixed curve OID and resolves it to an [`ECCCurve`].
+fn read_curve(data: &[u8]) -> Result<(&[u8], ECCCurve)> {
+    if data.is_empty() {
+        bail!("not enough data to read a curve OID length");
+    }
+    let len = usize::from(data[0]);
+    if data.len() < 1 + len {
+        bail!("not enough data to read a curve OID");
+    }
+
+    let curve = ECCCurve::from_oid(&data[1..1 + len])?;
+    Ok((&data[1 + len..], curve))
+}
+
+impl PublicParams {
+    /// Parses the algorithm-specific public parameters that follow a public-key packet's
+    /// algorithm octet.
+    ///
+    /// Algorithm ids this crate does not recognize (`PublicKeyAlgorithm::Unknown`, as well as
+    /// the private/experimental range) fall through to `PublicParams::Unknown`, retaining the
+    /// raw, unparsed bytes so the packet still round-trips byte-for-byte through `Serialize`
+    /// even though this crate can't use the key cryptographically.
+    pub fn from_slice(alg: PublicKeyAlgorithm, data: &[u8]) -> Result<PublicParams> {
+        match alg {
+            PublicKeyAlgorithm::RSA
+            | PublicKeyAlgorithm::RSAEncrypt
+            | PublicKeyAlgorithm::RSASign => {
+                let (data, n) = read_bignum_mpi(data)?;
+                let (_, e) = read_bignum_mpi(data)?;
+                Ok(PublicParams::RSA { n, e })
+            }
+            PublicKeyAlgorithm::DSA => {
+                let (data, p) = read_bignum_mpi(data)?;
+                let (data, q) = read_bignum_mpi(data)?;
+                let (data, g) = read_bignum_mpi(data)?;
+                let (_, y) = read_bignum_mpi(data)?;
+                Ok(PublicParams::DSA { p, q, g, y })
+            }
+            PublicKeyAlgorithm::ECDSA => {
+                let (data, curve) = read_curve(data)?;
+                let (_, p) = read_mpi(data)?;
+                Ok(PublicParams::ECDSA {
+                    curve,
+                    p: p.to_vec(),
+                })
+            }
+            PublicKeyAlgorithm::ECDH => {
+                let (data, curve) = read_curve(data)?;
+                let (data, p) = read_mpi(data)?;
+                if data.len() < 4 || data[0] != 0x03 || data[1] != 0x01 {
+                    bail!("invalid ECDH KDF parameters");
+                }
+                let hash = HashAlgorithm::from_u8(data[2])
+                    .ok_or_else(|| format_err!("unsupported ECDH KDF hash algorithm: {}", data[2]))?;
+                let alg_sym = SymmetricKeyAlgorithm::from_u8(data[3]).ok_or_else(|| {
+                    format_err!("unsupported ECDH KDF symmetric algorithm: {}", data[3])
+                })?;
+                Ok(PublicParams::ECDH {
+                    curve,
+                    p: p.to_vec(),
+                    hash,
+                    alg_sym,
+                })
+            }
+            PublicKeyAlgorithm::Elgamal | PublicKeyAlgorithm::ElgamalSign => {
+                let (data, p) = read_bignum_mpi(data)?;
+                let (data, g) = read_bignum_mpi(data)?;
+                let (_, y) = read_bignum_mpi(data)?;
+                Ok(PublicParams::Elgamal { p, g, y })
+            }
+            PublicKeyAlgorithm::EdDSA => {
+                let (data, curve) = read_curve(data)?;
+                let (_, q) = read_mpi(data)?;
+                Ok(PublicParams::EdDSA {
+                    curve,
+                    q: q.to_vec(),
+                })
+            }
+            PublicKeyAlgorithm::DiffieHellman => {
+                bail!("Diffie-Hellman public parameters are not supported");
+            }
+            PublicKeyAlgorithm::Private100
+            | PublicKeyAlgorithm::Private101
+            | PublicKeyAlgorithm::Private102
+            | PublicKeyAlgorithm::Private103
+            | PublicKeyAlgorithm::Private104
+            | PublicKeyAlgorithm::Private105
+            | PublicKeyAlgorithm::Private106
+            | PublicKeyAlgorithm::Private107
+            | PublicKeyAlgorithm::Private108
+            | PublicKeyAlgorithm::Private109
+            | PublicKeyAlgorithm::Private110
+            | PublicKeyAlgorithm::Unknown(_) => Ok(PublicParams::Unknown {
+                alg: alg.to_u8(),
+                data: data.to_vec(),
+            }),
+        }
+    }
 }
 
 impl Serialize for PublicParams {
@@ -143,6 +385,9 @@ impl Serialize for PublicParams {
 
                 write_mpi(q, writer)?;
             }
+            PublicParams::Unknown { ref data, .. } => {
+                writer.write_all(data)?;
+            }
         }
 
         Ok(())
@@ -202,6 +447,44 @@ impl fmt::Debug for PublicParams {
                 .field("curve", curve)
                 .field("q", &hex::encode(q))
                 .finish(),
+            PublicParams::Unknown { alg, ref data } => f
+                .debug_struct("PublicParams::Unknown")
+                .field("alg", alg)
+                .field("data", &hex::encode(data))
+                .finish(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_public_key_algorithm_from_primitive() {
+        assert_eq!(
+            PublicKeyAlgorithm::from_u8(200),
+            Some(PublicKeyAlgorithm::Unknown(200))
+        );
+        assert_eq!(PublicKeyAlgorithm::from_u8(1), Some(PublicKeyAlgorithm::RSA));
+    }
+
+    #[test]
+    fn unknown_public_params_roundtrip() {
+        let alg = PublicKeyAlgorithm::Unknown(200);
+        let data = vec![0xDE, 0xAD, 0xBE, 0xEF, 0x01, 0x02, 0x03];
+
+        let params = PublicParams::from_slice(alg, &data).expect("parses");
+        assert_eq!(
+            params,
+            PublicParams::Unknown {
+                alg: 200,
+                data: data.clone(),
+            }
+        );
+
+        let mut serialized = Vec::new();
+        params.to_writer(&mut serialized).expect("serializes");
+        assert_eq!(serialized, data);
+    }
+}